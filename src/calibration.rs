@@ -1,11 +1,20 @@
 use crate::types::Servo;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use strum::IntoEnumIterator;
 
+/// Default EWMA smoothing window, in samples.
+const DEFAULT_EWMA_WINDOW: u32 = 20;
+
+/// Number of resamples drawn by [`ServoCalibration::calibrate_with_ci`].
+const BOOTSTRAP_RESAMPLES: usize = 1000;
+
 #[derive(Debug, Clone)]
 pub struct ServoCalibration {
     pub(crate) positive_movement: f32,
     pub(crate) negative_movement: f32,
+    pub(crate) positive_accumulator: WelfordAccumulator,
+    pub(crate) negative_accumulator: WelfordAccumulator,
+    ewma_window: u32,
 }
 
 impl ServoCalibration {
@@ -13,9 +22,57 @@ impl ServoCalibration {
         ServoCalibration {
             positive_movement: 0.0,
             negative_movement: 0.0,
+            positive_accumulator: WelfordAccumulator::new(),
+            negative_accumulator: WelfordAccumulator::new(),
+            ewma_window: DEFAULT_EWMA_WINDOW,
         }
     }
 
+    /// Sets the EWMA smoothing window `N`; larger windows weight recent
+    /// samples less heavily.
+    pub fn set_ewma_window(&mut self, window: u32) {
+        self.ewma_window = window;
+    }
+
+    /// Folds one more positive-direction sample into the running accumulator
+    /// and refreshes `positive_movement` from its mean. A zero-size (or
+    /// otherwise non-finite) ratio is skipped rather than folded in, since
+    /// `WelfordAccumulator` has no way to recover from an `inf`/`NaN` mean
+    /// once one is pushed.
+    pub fn push_positive(&mut self, size: f32, error: f32) {
+        let Some(ratio) = finite_ratio(size, error) else { return };
+        self.positive_accumulator.push(ratio);
+        self.positive_movement = average_with_scaling_from_mean(self.positive_accumulator.mean());
+    }
+
+    /// Negative-direction counterpart to [`ServoCalibration::push_positive`].
+    pub fn push_negative(&mut self, size: f32, error: f32) {
+        let Some(ratio) = finite_ratio(size, error) else { return };
+        self.negative_accumulator.push(ratio);
+        self.negative_movement = average_with_scaling_from_mean(self.negative_accumulator.mean());
+    }
+
+    /// Nudges `positive_movement` toward one more `(size, error)` sample with
+    /// an EWMA (`current = w*next + (1-w)*current`, `w = 2/(1+N)`). Takes
+    /// `(size, error)` rather than a raw ratio so the blend stays in the same
+    /// scaled units as `positive_movement`. A zero-size (or otherwise
+    /// non-finite) ratio is skipped, since the recurrence can never recover
+    /// once `positive_movement` itself goes non-finite.
+    pub fn update_positive(&mut self, size: f32, error: f32) {
+        let Some(ratio) = finite_ratio(size, error) else { return };
+        let w = ewma_weight(self.ewma_window);
+        let scaled = average_with_scaling_from_mean(ratio);
+        self.positive_movement = w * scaled + (1.0 - w) * self.positive_movement;
+    }
+
+    /// Negative-direction counterpart to [`ServoCalibration::update_positive`].
+    pub fn update_negative(&mut self, size: f32, error: f32) {
+        let Some(ratio) = finite_ratio(size, error) else { return };
+        let w = ewma_weight(self.ewma_window);
+        let scaled = average_with_scaling_from_mean(ratio);
+        self.negative_movement = w * scaled + (1.0 - w) * self.negative_movement;
+    }
+
     pub fn calculate_from_movements(positive_errors: &[(f32, f32)], negative_errors: &[(f32, f32)]) -> Self {
         // Filter out outliers: Remove measurements where error/size ratio is more than 2 standard deviations from mean
         let filter_outliers = |measurements: &[(f32, f32)]| -> Vec<f32> {
@@ -23,17 +80,23 @@ impl ServoCalibration {
                 return vec![];
             }
 
-            // Calculate ratios
+            // Calculate ratios, dropping non-finite ones from a zero-size sample
             let ratios: Vec<f32> = measurements.iter()
-                .map(|(size, error)| error / size)
+                .filter_map(|&(size, error)| finite_ratio(size, error))
                 .collect();
 
-            // Calculate mean and standard deviation
-            let mean = ratios.iter().sum::<f32>() / ratios.len() as f32;
-            let variance = ratios.iter()
-                .map(|&x| (x - mean).powi(2))
-                .sum::<f32>() / ratios.len() as f32;
-            let std_dev = variance.sqrt();
+            if ratios.is_empty() {
+                return vec![];
+            }
+
+            // Calculate mean and standard deviation via the same running
+            // accumulator `push_positive`/`push_negative` use
+            let mut acc = WelfordAccumulator::new();
+            for &ratio in &ratios {
+                acc.push(ratio);
+            }
+            let mean = acc.mean();
+            let std_dev = acc.std_dev();
 
             // Filter outliers
             ratios.into_iter()
@@ -41,61 +104,586 @@ impl ServoCalibration {
                 .collect()
         };
 
-        // Apply adaptive scaling factor based on error magnitude
-        let calculate_scaling = |avg_error: f32| -> f32 {
-            // Smaller corrections for larger errors to prevent overshooting
-            if avg_error.abs() > 0.5 {
-                5.0
-            } else if avg_error.abs() > 0.2 {
-                7.0
-            } else {
-                10.0
-            }
-        };
-
         let pos_ratios = filter_outliers(positive_errors);
         let neg_ratios = filter_outliers(negative_errors);
 
-        let pos_avg = if !pos_ratios.is_empty() {
-            let avg = pos_ratios.iter().sum::<f32>() / pos_ratios.len() as f32;
-            avg * calculate_scaling(avg)
-        } else {
-            0.0
-        };
-
-        let neg_avg = if !neg_ratios.is_empty() {
-            let avg = neg_ratios.iter().sum::<f32>() / neg_ratios.len() as f32;
-            avg * calculate_scaling(avg)
-        } else {
-            0.0
-        };
+        let pos_avg = average_with_scaling(&pos_ratios);
+        let neg_avg = average_with_scaling(&neg_ratios);
 
         ServoCalibration {
             positive_movement: pos_avg,
             negative_movement: neg_avg,
+            positive_accumulator: WelfordAccumulator::new(),
+            negative_accumulator: WelfordAccumulator::new(),
+            ewma_window: DEFAULT_EWMA_WINDOW,
+        }
+    }
+
+    /// Distribution-agnostic alternative to [`ServoCalibration::calculate_from_movements`]:
+    /// rejects outliers with Tukey fences over the error/size ratios instead
+    /// of a mean ± 2σ test. Mild outliers (outside `[Q1 - 1.5*IQR, Q3 +
+    /// 1.5*IQR]`) are kept only when `keep_mild_outliers` is set; severe ones
+    /// (outside the 3*IQR fence) are always dropped.
+    pub fn calculate_from_movements_tukey(
+        positive_errors: &[(f32, f32)],
+        negative_errors: &[(f32, f32)],
+        keep_mild_outliers: bool,
+    ) -> Self {
+        let pos_ratios = tukey_filter(positive_errors, keep_mild_outliers);
+        let neg_ratios = tukey_filter(negative_errors, keep_mild_outliers);
+
+        ServoCalibration {
+            positive_movement: average_with_scaling(&pos_ratios),
+            negative_movement: average_with_scaling(&neg_ratios),
+            positive_accumulator: WelfordAccumulator::new(),
+            negative_accumulator: WelfordAccumulator::new(),
+            ewma_window: DEFAULT_EWMA_WINDOW,
+        }
+    }
+
+    /// Replaces the magic scaling heuristic in [`ServoCalibration::calculate_from_movements`]
+    /// with a through-the-origin least-squares fit `error = slope * size`.
+    /// Returns the fitted calibration alongside the goodness-of-fit R² for
+    /// each direction.
+    pub fn calculate_from_movements_regression(
+        positive_errors: &[(f32, f32)],
+        negative_errors: &[(f32, f32)],
+    ) -> (Self, f32, f32) {
+        let positive_slope = through_origin_slope(positive_errors);
+        let negative_slope = through_origin_slope(negative_errors);
+
+        let positive_r_squared = r_squared(positive_errors, positive_slope);
+        let negative_r_squared = r_squared(negative_errors, negative_slope);
+
+        let calibration = ServoCalibration {
+            positive_movement: positive_slope,
+            negative_movement: negative_slope,
+            positive_accumulator: WelfordAccumulator::new(),
+            negative_accumulator: WelfordAccumulator::new(),
+            ewma_window: DEFAULT_EWMA_WINDOW,
+        };
+
+        (calibration, positive_r_squared, negative_r_squared)
+    }
+
+    /// Bootstraps a 95% confidence interval around [`ServoCalibration::calculate_from_movements`]'s
+    /// point estimate, reporting the 2.5th/97.5th percentiles of
+    /// [`BOOTSTRAP_RESAMPLES`] resampled-with-replacement estimates. `rng`
+    /// drives the resampling so callers (and tests) can reproduce a specific
+    /// run.
+    pub fn calibrate_with_ci(
+        positive_errors: &[(f32, f32)],
+        negative_errors: &[(f32, f32)],
+        rng: &mut Rng,
+    ) -> (Self, (f32, f32), (f32, f32)) {
+        let calibration = Self::calculate_from_movements(positive_errors, negative_errors);
+
+        let positive_ci = bootstrap_ci(positive_errors, rng);
+        let negative_ci = bootstrap_ci(negative_errors, rng);
+
+        (calibration, positive_ci, negative_ci)
+    }
+}
+
+/// EWMA weight `w = 2/(1+N)` for a smoothing window of `N` samples.
+fn ewma_weight(window: u32) -> f32 {
+    2.0 / (1.0 + window.max(1) as f32)
+}
+
+/// `error / size`, or `None` for a zero-size (or otherwise non-finite)
+/// sample. Shared by `push_positive`/`push_negative`/`update_positive`/`update_negative`
+/// so a poisoned ratio is rejected the same way in all four.
+fn finite_ratio(size: f32, error: f32) -> Option<f32> {
+    let ratio = error / size;
+    ratio.is_finite().then_some(ratio)
+}
+
+/// Seedable xorshift64 PRNG for reproducible bootstrap resampling; not
+/// suitable for anything security-sensitive.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng {
+            state: if seed == 0 { 0x9E3779B97F4A7C15 } else { seed },
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// Uniform index in `[0, bound)`.
+    fn gen_range(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// Bootstraps a 95% confidence interval for the scaled-average error/size
+/// ratio over `measurements` by resampling with replacement
+/// [`BOOTSTRAP_RESAMPLES`] times.
+fn bootstrap_ci(measurements: &[(f32, f32)], rng: &mut Rng) -> (f32, f32) {
+    if measurements.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let mut estimates: Vec<f32> = (0..BOOTSTRAP_RESAMPLES)
+        .map(|_| {
+            let resample: Vec<f32> = (0..measurements.len())
+                .filter_map(|_| {
+                    let (size, error) = measurements[rng.gen_range(measurements.len())];
+                    finite_ratio(size, error)
+                })
+                .collect();
+            average_with_scaling(&resample)
+        })
+        .collect();
+
+    estimates.sort_by(|a, b| a.total_cmp(b));
+    (percentile(&estimates, 0.025), percentile(&estimates, 0.975))
+}
+
+/// Through-the-origin least-squares slope `error = slope * size`, i.e.
+/// `slope = sum(size*error) / sum(size^2)`.
+fn through_origin_slope(measurements: &[(f32, f32)]) -> f32 {
+    let sum_size_error: f32 = measurements.iter().map(|(size, error)| size * error).sum();
+    let sum_size_squared: f32 = measurements.iter().map(|(size, _)| size * size).sum();
+
+    if sum_size_squared == 0.0 {
+        0.0
+    } else {
+        sum_size_error / sum_size_squared
+    }
+}
+
+/// Goodness-of-fit `R² = 1 - Σ(residual²) / Σ((error - mean_error)²)` for the
+/// through-the-origin fit `error = slope * size`.
+fn r_squared(measurements: &[(f32, f32)], slope: f32) -> f32 {
+    if measurements.is_empty() {
+        return 0.0;
+    }
+
+    let mean_error = measurements.iter().map(|(_, error)| error).sum::<f32>() / measurements.len() as f32;
+
+    let residual_sum_squares: f32 = measurements.iter()
+        .map(|(size, error)| (error - slope * size).powi(2))
+        .sum();
+    let total_sum_squares: f32 = measurements.iter()
+        .map(|(_, error)| (error - mean_error).powi(2))
+        .sum();
+
+    if total_sum_squares == 0.0 {
+        1.0
+    } else {
+        1.0 - residual_sum_squares / total_sum_squares
+    }
+}
+
+/// Mean of `ratios`, scaled by [`scaling_for`] based on its own magnitude.
+fn average_with_scaling(ratios: &[f32]) -> f32 {
+    if ratios.is_empty() {
+        return 0.0;
+    }
+    let avg = ratios.iter().sum::<f32>() / ratios.len() as f32;
+    average_with_scaling_from_mean(avg)
+}
+
+/// Same scaling as [`average_with_scaling`], applied directly to an
+/// already-computed mean (e.g. from a [`WelfordAccumulator`]).
+fn average_with_scaling_from_mean(mean: f32) -> f32 {
+    mean * scaling_for(mean)
+}
+
+/// Online mean/variance accumulator over the error/size ratios, updated
+/// incrementally with Welford's algorithm.
+#[derive(Debug, Clone)]
+pub struct WelfordAccumulator {
+    n: u32,
+    mean: f32,
+    m2: f32,
+}
+
+impl WelfordAccumulator {
+    pub fn new() -> Self {
+        WelfordAccumulator { n: 0, mean: 0.0, m2: 0.0 }
+    }
+
+    pub fn push(&mut self, ratio: f32) {
+        self.n += 1;
+        let delta = ratio - self.mean;
+        self.mean += delta / self.n as f32;
+        let delta2 = ratio - self.mean;
+        self.m2 += delta * delta2;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.n
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.mean
+    }
+
+    pub fn variance(&self) -> f32 {
+        if self.n == 0 {
+            0.0
+        } else {
+            self.m2 / self.n as f32
         }
     }
+
+    pub fn std_dev(&self) -> f32 {
+        self.variance().sqrt()
+    }
+}
+
+impl Default for WelfordAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adaptive scaling factor based on error magnitude: smaller corrections for
+/// larger errors, to prevent overshooting.
+fn scaling_for(avg_error: f32) -> f32 {
+    if avg_error.abs() > 0.5 {
+        5.0
+    } else if avg_error.abs() > 0.2 {
+        7.0
+    } else {
+        10.0
+    }
+}
+
+/// Linearly-interpolated percentile of an already-sorted slice, matching the
+/// common "linear" percentile method (e.g. NumPy's default).
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    if sorted.len() == 1 {
+        return sorted[0];
+    }
+
+    let rank = p * (sorted.len() - 1) as f32;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    if lower == upper {
+        sorted[lower]
+    } else {
+        let frac = rank - lower as f32;
+        let diff = sorted[upper] - sorted[lower];
+        // `diff` is `inf - inf` (NaN) when both straddling ranks land on a
+        // run of non-finite estimates from a zero-size sample; fall back to
+        // the lower rank rather than propagate a NaN percentile.
+        if diff.is_finite() {
+            sorted[lower] + frac * diff
+        } else {
+            sorted[lower]
+        }
+    }
+}
+
+/// Rejects outliers in the error/size ratios of `measurements` using Tukey
+/// fences. Non-finite ratios (e.g. from a zero-size sample) are dropped up
+/// front via [`finite_ratio`], before the fewer-than-4-samples fallback that
+/// keeps every remaining ratio as-is.
+fn tukey_filter(measurements: &[(f32, f32)], keep_mild_outliers: bool) -> Vec<f32> {
+    if measurements.is_empty() {
+        return vec![];
+    }
+
+    let mut ratios: Vec<f32> = measurements.iter()
+        .filter_map(|&(size, error)| finite_ratio(size, error))
+        .collect();
+
+    if ratios.len() < 4 {
+        return ratios;
+    }
+
+    ratios.sort_by(|a, b| a.total_cmp(b));
+
+    let q1 = percentile(&ratios, 0.25);
+    let q3 = percentile(&ratios, 0.75);
+    let iqr = q3 - q1;
+
+    let (mild_k, severe_k) = (1.5, 3.0);
+    let severe_lower = q1 - severe_k * iqr;
+    let severe_upper = q3 + severe_k * iqr;
+    let mild_lower = q1 - mild_k * iqr;
+    let mild_upper = q3 + mild_k * iqr;
+
+    ratios.into_iter()
+        .filter(|&ratio| {
+            if ratio < severe_lower || ratio > severe_upper {
+                return false; // severe outlier: always dropped
+            }
+            if ratio < mild_lower || ratio > mild_upper {
+                return keep_mild_outliers; // mild outlier: caller's choice
+            }
+            true
+        })
+        .collect()
 }
 
+/// Maximum number of recent (size, error) samples retained per servo per
+/// direction, so a long calibration session runs in bounded rather than
+/// unbounded memory. [`CalibrationData::record_positive`]/
+/// [`CalibrationData::record_negative`] fold every sample into the servo's
+/// `ServoCalibration` Welford accumulator in O(1) regardless of this cap, so
+/// the point estimate is never starved of data between recalibrations; this
+/// window only limits how far back [`CalibrationData::recalibrate_with_ci`]'s
+/// outlier-filtered recompute and bootstrap CI can see.
+const MOVEMENT_HISTORY_CAP: usize = 500;
+
 #[derive(Default)]
 pub(crate) struct CalibrationData {
     pub calibrations: HashMap<Servo, ServoCalibration>,
     pub collecting_data: bool,
-    pub movement_data: HashMap<Servo, (Vec<(f32, f32)>, Vec<(f32, f32)>)>,
+    recent_movements: HashMap<Servo, (VecDeque<(f32, f32)>, VecDeque<(f32, f32)>)>,
+    /// Per-servo (positive, negative) confidence intervals from the most
+    /// recent [`CalibrationData::recalibrate_with_ci`] run.
+    pub calibration_cis: HashMap<Servo, ((f32, f32), (f32, f32))>,
 }
 
 impl CalibrationData {
     pub fn new() -> Self {
         let mut calibrations = HashMap::new();
-        let mut movement_data = HashMap::new();
+        let mut recent_movements = HashMap::new();
         for servo in Servo::iter() {
             calibrations.insert(servo, ServoCalibration::new());
-            movement_data.insert(servo, (Vec::new(), Vec::new()));
+            recent_movements.insert(servo, (VecDeque::new(), VecDeque::new()));
         }
         CalibrationData {
             calibrations,
             collecting_data: false,
-            movement_data,
+            recent_movements,
+            calibration_cis: HashMap::new(),
         }
     }
-}
\ No newline at end of file
+
+    /// Records one positive-direction `(size, error)` sample for `servo`:
+    /// folds it into that servo's running [`ServoCalibration::push_positive`]
+    /// accumulator (updating `calibrations` immediately, in O(1), though
+    /// without outlier rejection) and keeps it in the bounded
+    /// [`MOVEMENT_HISTORY_CAP`] window [`CalibrationData::recalibrate_with_ci`]
+    /// periodically recomputes an outlier-filtered estimate from.
+    pub fn record_positive(&mut self, servo: Servo, size: f32, error: f32) {
+        if let Some(calibration) = self.calibrations.get_mut(&servo) {
+            calibration.push_positive(size, error);
+        }
+        let (positive, _) = self.recent_movements.entry(servo).or_default();
+        positive.push_back((size, error));
+        if positive.len() > MOVEMENT_HISTORY_CAP {
+            positive.pop_front();
+        }
+    }
+
+    /// Negative-direction counterpart to [`CalibrationData::record_positive`].
+    pub fn record_negative(&mut self, servo: Servo, size: f32, error: f32) {
+        if let Some(calibration) = self.calibrations.get_mut(&servo) {
+            calibration.push_negative(size, error);
+        }
+        let (_, negative) = self.recent_movements.entry(servo).or_default();
+        negative.push_back((size, error));
+        if negative.len() > MOVEMENT_HISTORY_CAP {
+            negative.pop_front();
+        }
+    }
+
+    /// Recalibrates every servo from the bounded window of recent samples via
+    /// [`ServoCalibration::calibrate_with_ci`], updating both `calibrations`
+    /// and `calibration_cis`. `record_positive`/`record_negative` already
+    /// keep `calibrations` current between recalibrations in O(1), but that
+    /// running mean applies no outlier rejection; this periodic 2σ-filtered
+    /// recompute is what corrects for a single jammed/misread sample skewing
+    /// it, the same protection `calculate_from_movements` always gave.
+    pub fn recalibrate_with_ci(&mut self, rng: &mut Rng) {
+        for (servo, (positive, negative)) in &self.recent_movements {
+            let positive_errors: Vec<(f32, f32)> = positive.iter().copied().collect();
+            let negative_errors: Vec<(f32, f32)> = negative.iter().copied().collect();
+            let (calibration, positive_ci, negative_ci) =
+                ServoCalibration::calibrate_with_ci(&positive_errors, &negative_errors, rng);
+            self.calibrations.insert(*servo, calibration);
+            self.calibration_cis.insert(*servo, (positive_ci, negative_ci));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn welford_accumulator_matches_known_mean_and_variance() {
+        let mut acc = WelfordAccumulator::new();
+        for ratio in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            acc.push(ratio);
+        }
+        assert_eq!(acc.count(), 8);
+        assert!((acc.mean() - 5.0).abs() < 1e-4);
+        assert!((acc.variance() - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn welford_accumulator_empty_is_zero() {
+        let acc = WelfordAccumulator::new();
+        assert_eq!(acc.mean(), 0.0);
+        assert_eq!(acc.std_dev(), 0.0);
+    }
+
+    #[test]
+    fn push_positive_ignores_zero_size_samples_without_panicking() {
+        let mut calibration = ServoCalibration::new();
+        calibration.push_positive(1.0, 1.0);
+        calibration.push_positive(0.0, 1.0);
+        calibration.push_positive(1.0, 1.1);
+        assert!(calibration.positive_movement.is_finite());
+        assert_eq!(calibration.positive_accumulator.count(), 2);
+    }
+
+    #[test]
+    fn push_negative_ignores_zero_size_samples_without_panicking() {
+        let mut calibration = ServoCalibration::new();
+        calibration.push_negative(1.0, 1.0);
+        calibration.push_negative(0.0, 1.0);
+        calibration.push_negative(1.0, 0.9);
+        assert!(calibration.negative_movement.is_finite());
+        assert_eq!(calibration.negative_accumulator.count(), 2);
+    }
+
+    #[test]
+    fn update_positive_ignores_zero_size_samples_without_panicking() {
+        let mut calibration = ServoCalibration::new();
+        calibration.update_positive(1.0, 1.0);
+        calibration.update_positive(0.0, 1.0);
+        calibration.update_positive(1.0, 1.1);
+        assert!(calibration.positive_movement.is_finite());
+    }
+
+    #[test]
+    fn update_negative_ignores_zero_size_samples_without_panicking() {
+        let mut calibration = ServoCalibration::new();
+        calibration.update_negative(1.0, 1.0);
+        calibration.update_negative(0.0, 1.0);
+        calibration.update_negative(1.0, 0.9);
+        assert!(calibration.negative_movement.is_finite());
+    }
+
+    #[test]
+    fn ewma_weight_shrinks_as_window_grows() {
+        assert_eq!(ewma_weight(1), 1.0);
+        assert!(ewma_weight(20) < ewma_weight(1));
+    }
+
+    #[test]
+    fn tukey_filter_drops_a_severe_outlier() {
+        let measurements: Vec<(f32, f32)> = [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 100.0]
+            .iter()
+            .map(|&error| (1.0, error))
+            .collect();
+        let ratios = tukey_filter(&measurements, false);
+        assert!(!ratios.contains(&100.0));
+    }
+
+    #[test]
+    fn tukey_filter_ignores_non_finite_ratios_without_panicking() {
+        let measurements = [(1.0, 1.0), (1.0, 1.1), (0.0, 1.0), (1.0, 0.9), (1.0, 1.2)];
+        let ratios = tukey_filter(&measurements, false);
+        assert!(ratios.iter().all(|r| r.is_finite()));
+    }
+
+    #[test]
+    fn tukey_filter_drops_non_finite_ratios_under_the_four_sample_fallback() {
+        let measurements = [(0.0, 1.0), (1.0, 1.0), (1.0, 1.1)];
+        let ratios = tukey_filter(&measurements, false);
+        assert!(ratios.iter().all(|r| r.is_finite()));
+        assert_eq!(ratios.len(), 2);
+    }
+
+    #[test]
+    fn through_origin_slope_recovers_a_known_slope() {
+        let measurements = [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0)];
+        assert!((through_origin_slope(&measurements) - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn r_squared_is_one_for_a_perfect_fit() {
+        let measurements = [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0)];
+        assert!((r_squared(&measurements, 2.0) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bootstrap_ci_is_reproducible_with_the_same_seed() {
+        let measurements = [(1.0, 1.0), (1.0, 1.1), (1.0, 0.9), (1.0, 1.05)];
+        let ci_a = bootstrap_ci(&measurements, &mut Rng::new(42));
+        let ci_b = bootstrap_ci(&measurements, &mut Rng::new(42));
+        assert_eq!(ci_a, ci_b);
+    }
+
+    #[test]
+    fn bootstrap_ci_ignores_zero_size_samples_without_panicking() {
+        let measurements = [(1.0, 1.0), (0.0, 1.0), (1.0, 0.9)];
+        let (lower, upper) = bootstrap_ci(&measurements, &mut Rng::new(1));
+        assert!(lower.is_finite() && upper.is_finite());
+        assert!(lower <= upper);
+    }
+
+    #[test]
+    fn percentile_linearly_interpolates_between_ranks() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 1.0), 4.0);
+        assert!((percentile(&sorted, 0.5) - 2.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn record_positive_updates_the_running_calibration_immediately() {
+        let mut data = CalibrationData::new();
+        data.record_positive(Servo::WristTilt, 1.0, 1.0);
+        data.record_positive(Servo::WristTilt, 1.0, 1.1);
+
+        let calibration = &data.calibrations[&Servo::WristTilt];
+        assert!(calibration.positive_movement.is_finite());
+        assert_eq!(calibration.positive_accumulator.count(), 2);
+    }
+
+    #[test]
+    fn record_positive_caps_recent_movements_at_the_history_window() {
+        let mut data = CalibrationData::new();
+        for _ in 0..(MOVEMENT_HISTORY_CAP + 10) {
+            data.record_positive(Servo::WristTilt, 1.0, 1.0);
+        }
+
+        let (positive, _) = &data.recent_movements[&Servo::WristTilt];
+        assert_eq!(positive.len(), MOVEMENT_HISTORY_CAP);
+    }
+
+    #[test]
+    fn recalibrate_with_ci_corrects_the_running_estimate_for_an_outlier() {
+        let mut data = CalibrationData::new();
+        for _ in 0..9 {
+            data.record_positive(Servo::WristTilt, 1.0, 1.0);
+        }
+        // A single jammed/misread sample skews the uncorrected running mean.
+        data.record_positive(Servo::WristTilt, 1.0, 100.0);
+        let skewed = data.calibrations[&Servo::WristTilt].positive_movement;
+
+        data.recalibrate_with_ci(&mut Rng::new(1));
+        let corrected = data.calibrations[&Servo::WristTilt].positive_movement;
+
+        assert!(corrected < skewed);
+    }
+
+    #[test]
+    fn calculate_from_movements_ignores_zero_size_samples_without_panicking() {
+        let measurements = [(1.0, 1.0), (0.0, 1.0), (1.0, 1.1)];
+        let calibration = ServoCalibration::calculate_from_movements(&measurements, &measurements);
+        assert!(calibration.positive_movement.is_finite());
+        assert!(calibration.negative_movement.is_finite());
+    }
+}
@@ -1,194 +1,212 @@
 // src/controller.rs
 use crate::{
     constants::*,
-    types::{clamp_angle, JointAngles, Servo},
+    transport::{Transport, TransportError},
+    types::{angle_to_position, position_to_angle, JointAngles, Servo},
 };
-use hidapi::HidApi;
 use std::collections::HashMap;
 use std::error::Error;
-use std::fmt;
 
+/// High-level xArm API. Owns a [`Transport`] and routes all framing through
+/// it, so the same API works over both USB and BLE.
 pub struct Controller {
-    device: hidapi::HidDevice,
+    transport: Transport,
+    timeline: Timeline,
 }
-// Custom error type for better error messages
-#[derive(Debug)]
-pub enum ControllerError {
-    InvalidResponse {
-        expected_len: usize,
-        actual_len: usize,
-        raw_data: Vec<u8>,
-    },
-    DeviceError(String),
-}
-
-impl fmt::Display for ControllerError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ControllerError::InvalidResponse { expected_len, actual_len, raw_data } => {
-                write!(f, "Invalid response data: expected length {} but got {}. Raw data: {:02x?}",
-                       expected_len, actual_len, raw_data)
-            }
-            ControllerError::DeviceError(msg) => write!(f, "Device error: {}", msg),
-        }
-    }
-}
-
-impl Error for ControllerError {}
 
 impl Controller {
-    pub fn new() -> Result<Self, Box<dyn Error>> {
-        let api = HidApi::new()?;
-        let device = api.open(VENDOR_ID, PRODUCT_ID)?;
-
-        Ok(Controller {
-            device,
-        })
+    pub async fn new() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let transport = Transport::new().await?;
+        Ok(Controller { transport, timeline: Timeline::new() })
     }
 
-    fn _send(&mut self, cmd: u8, data: &[u8]) -> Result<(), Box<dyn Error>> {
-        let mut report_data = vec![0, SIGNATURE, SIGNATURE, (data.len() + 2) as u8, cmd];
-        report_data.extend_from_slice(data);
-        self.device.write(&report_data)?;
-        Ok(())
+    async fn _send(&mut self, cmd: u8, data: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.transport.send(cmd, data).await
     }
 
-    fn _recv(&mut self, cmd: u8) -> Result<Vec<u8>, Box<dyn Error>> {
-        let mut buf = [0u8; 64];
-        let res = self.device.read_timeout(&mut buf, 1000)?;
-
-        if res < 4 {
-            return Err(ControllerError::InvalidResponse {
-                expected_len: 4,
-                actual_len: res,
-                raw_data: buf[..res].to_vec(),
-            }.into());
-        }
-
-        if buf[0] != SIGNATURE || buf[1] != SIGNATURE {
-            return Err(ControllerError::DeviceError(
-                format!("Invalid signature: {:02x} {:02x}", buf[0], buf[1])
-            ).into());
-        }
-
-        if buf[3] != cmd {
-            return Err(ControllerError::DeviceError(
-                format!("Command mismatch: expected {:02x}, got {:02x}", cmd, buf[3])
-            ).into());
-        }
-
-        let length = buf[2] as usize;
-        if res < 4 + length {
-            return Err(ControllerError::InvalidResponse {
-                expected_len: 4 + length,
-                actual_len: res,
-                raw_data: buf[..res].to_vec(),
-            }.into());
-        }
-
-        Ok(buf[4..4 + length].to_vec())
+    async fn _recv(&mut self, cmd: u8) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        self.transport.recv(cmd).await
     }
-    pub fn get_battery_voltage(&mut self) -> Result<f32, Box<dyn Error>> {
-        self._send(CMD_GET_BATTERY_VOLTAGE, &[])?;
-        let data = self._recv(CMD_GET_BATTERY_VOLTAGE)?;
+
+    pub async fn get_battery_voltage(&mut self) -> Result<f32, Box<dyn Error + Send + Sync>> {
+        self._send(CMD_GET_BATTERY_VOLTAGE, &[]).await?;
+        let data = self._recv(CMD_GET_BATTERY_VOLTAGE).await?;
         if data.len() >= 2 {
             Ok(((data[1] as u16 * 256 + data[0] as u16) as f32) / 1000.0)
         } else {
-            Err("Invalid battery voltage data".into())
+            Err(Box::new(TransportError::DeviceError("Invalid battery voltage data".into())))
         }
     }
 
-    fn _angle_to_position(angle: f32) -> u16 {
-        ((angle + 125.0) * 1000.0 / 250.0) as u16
+    pub async fn get_positions(&mut self, servos: &[Servo]) -> Result<HashMap<Servo, f32>, Box<dyn Error + Send + Sync>> {
+        query_positions(&mut self.transport, servos).await
     }
 
-    fn _position_to_angle(position: u16) -> f32 {
-        (position as f32) * 250.0 / 1000.0 - 125.0
+    pub async fn servo_off(&mut self, servo_id: Option<u8>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let data = match servo_id {
+            Some(id) => vec![1u8, id],
+            None => vec![6u8, 1, 2, 3, 4, 5, 6],
+        };
+        self._send(CMD_SERVO_STOP, &data).await?;
+        Ok(())
     }
 
-    pub fn get_positions(&mut self, servos: &[Servo]) -> Result<HashMap<Servo, f32>, Box<dyn Error>> {
-        if servos.is_empty() {
-            return Ok(HashMap::new());
-        }
-
-        let mut data = vec![servos.len() as u8];
-        for &servo in servos {
-            data.push(servo as u8);
-        }
+    pub fn calculate_joint_angles(&self, target_elevation: f32) -> JointAngles {
+        crate::types::calculate_joint_angles(target_elevation)
+    }
 
-        self._send(CMD_GET_SERVO_POSITION, &data)?;
+    pub async fn set_look(&mut self, target_elevation: f32, target_azimuth: f32) -> Result<u32, Box<dyn Error + Send + Sync>> {
+        let movements = crate::types::look_movements(target_elevation, target_azimuth);
+        self.set_multiple_positions(&movements).await
+    }
 
-        let response = self._recv(CMD_GET_SERVO_POSITION)?;
+    pub async fn set_multiple_positions(&mut self, movements: &[(Servo, f32)]) -> Result<u32, Box<dyn Error + Send + Sync>> {
+        move_with_retry(&mut self.transport, movements).await
+    }
 
-        let mut positions = HashMap::with_capacity(servos.len());
-        let mut response_idx = 1; // Skip the count byte
+    /// Plays back a compiled `Trajectory`, emitting each segment's
+    /// `CMD_SERVO_MOVE` and sleeping only the precompiled duration, with no
+    /// intermediate `get_positions` round-trips.
+    pub async fn play(&mut self, trajectory: &Trajectory, loops: u32) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for _ in 0..loops.max(1) {
+            for segment in &trajectory.segments {
+                self._send(CMD_SERVO_MOVE, &segment.data).await?;
+                tokio::time::sleep(tokio::time::Duration::from_millis(segment.duration_ms as u64)).await;
+            }
+        }
+        Ok(())
+    }
 
-        while response_idx + 2 < response.len() {
-            let servo_id = response[response_idx];
-            let position_low = response[response_idx + 1];
-            let position_high = response[response_idx + 2];
+    /// Records `movements` into this controller's timeline to start at
+    /// `start_ms`, measured from the instant `run_timeline` begins playback.
+    pub fn schedule_at(&mut self, start_ms: u64, movements: &[(Servo, f32)]) {
+        for &(servo, angle) in movements {
+            self.timeline.events.push((start_ms, servo, angle));
+        }
+    }
 
-            // Convert servo_id back to Servo enum
-            if let Some(servo) = servos.iter().find(|&&s| s as u8 == servo_id) {
-                let position = (position_high as u16) * 256 + position_low as u16;
-                let angle = Self::_position_to_angle(position);
-                positions.insert(*servo, angle);
+    /// Drains the events recorded by [`Controller::schedule_at`], sorts and
+    /// coalesces them, then sleeps out each event's wall-clock offset before
+    /// firing its `CMD_SERVO_MOVE`. Late events are logged as slack
+    /// violations rather than silently reordered; each move's duration comes
+    /// from its angular distance from that servo's last-commanded angle.
+    pub async fn run_timeline(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let events = std::mem::take(&mut self.timeline.events);
+        let merged = coalesce_timeline_events(events);
+
+        let start_instant = tokio::time::Instant::now();
+        let mut last_angle: HashMap<Servo, f32> = HashMap::new();
+        for (start_ms, servo, angle) in merged {
+            let target = tokio::time::Duration::from_millis(start_ms);
+            let elapsed = start_instant.elapsed();
+            if elapsed > target {
+                println!(
+                    "Slack violation: {:?} event scheduled for {}ms fired {}ms late",
+                    servo,
+                    start_ms,
+                    (elapsed - target).as_millis()
+                );
+            } else {
+                tokio::time::sleep(target - elapsed).await;
             }
 
-            response_idx += 3;
+            let movement_size = match last_angle.get(&servo) {
+                Some(&previous) => (angle - previous).abs(),
+                None => 0.0,
+            };
+            let duration_ms = ((movement_size * ANGULAR_SPEED_DEG_PER_MS).round() as u16).max(20);
+            last_angle.insert(servo, angle);
+
+            validate_angle(angle)?;
+            let position = angle_to_position(angle);
+            let data = vec![
+                1u8,
+                (duration_ms & 0xff) as u8,
+                ((duration_ms & 0xff00) >> 8) as u8,
+                servo as u8,
+                (position & 0xff) as u8,
+                ((position & 0xff00) >> 8) as u8,
+            ];
+            self._send(CMD_SERVO_MOVE, &data).await?;
         }
 
-        if positions.len() != servos.len() {
-            println!("Warning: Only got positions for {}/{} servos",
-                     positions.len(), servos.len());
-        }
+        Ok(())
+    }
+}
 
-        Ok(positions)
+/// Degrees per millisecond used to derive a move's duration from its angular
+/// distance. Shared by direct moves, the timeline, and precompiled
+/// trajectories.
+const ANGULAR_SPEED_DEG_PER_MS: f32 = 5.0;
+
+/// Rejects `angle` outside the servos' `-125.0..=125.0` range. Shared by
+/// `move_with_retry`, `Controller::run_timeline`, and `Trajectory::compile`
+/// so none of them hand `angle_to_position` a value that silently encodes to
+/// a bogus raw position.
+fn validate_angle(angle: f32) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if !(-125.0..=125.0).contains(&angle) {
+        return Err(format!("Angle {} must be between -125.0 and 125.0 degrees", angle).into());
     }
+    Ok(())
+}
 
-    pub fn servo_off(&mut self, servo_id: Option<u8>) -> Result<(), Box<dyn Error>> {
-        let data = match servo_id {
-            Some(id) => vec![1u8, id],
-            None => vec![6u8, 1, 2, 3, 4, 5, 6],
-        };
-        self._send(CMD_SERVO_STOP, &data)?;
-        Ok(())
+/// Fetches the current angle of each of `servos` over `transport`. Shared by
+/// `Controller` and `ControllerPool`.
+pub(crate) async fn query_positions(
+    transport: &mut Transport,
+    servos: &[Servo],
+) -> Result<HashMap<Servo, f32>, Box<dyn Error + Send + Sync>> {
+    if servos.is_empty() {
+        return Ok(HashMap::new());
     }
 
-    pub fn calculate_joint_angles(&self, target_elevation: f32) -> JointAngles {
-        let target_elevation = target_elevation.max(MIN_ELEVATION).min(MAX_ELEVATION);
-        let target_total_angle = 90.0 - target_elevation;
-        let shoulder = clamp_angle(-target_total_angle * 0.4);
-        let elbow = clamp_angle(target_total_angle * 0.8);
-        let wrist = clamp_angle(target_total_angle - shoulder - elbow);
-
-        JointAngles {
-            shoulder: (shoulder * 10.0).round() / 10.0,
-            elbow: -(elbow * 10.0).round() / 10.0,
-            wrist: (wrist * 10.0).round() / 10.0,
-        }
+    let mut data = vec![servos.len() as u8];
+    for &servo in servos {
+        data.push(servo as u8);
     }
 
-    pub fn set_look(&mut self, target_elevation: f32, target_azimuth: f32) -> Result<u32, Box<dyn Error>> {
-        let angles = self.calculate_joint_angles(target_elevation);
+    transport.send(CMD_GET_SERVO_POSITION, &data).await?;
+    let response = transport.recv(CMD_GET_SERVO_POSITION).await?;
 
-        let movements = vec![
-            (Servo::WristTilt, angles.wrist),
-            (Servo::ElbowTilt, angles.elbow),
-            (Servo::ShoulderTilt, angles.shoulder),
-            (Servo::BaseSpin, target_azimuth),
-        ];
+    let mut positions = HashMap::with_capacity(servos.len());
+    let mut response_idx = 1; // Skip the count byte
+
+    while response_idx + 2 < response.len() {
+        let servo_id = response[response_idx];
+        let position_low = response[response_idx + 1];
+        let position_high = response[response_idx + 2];
 
-        self.set_multiple_positions(&movements)
+        // Convert servo_id back to Servo enum
+        if let Some(servo) = servos.iter().find(|&&s| s as u8 == servo_id) {
+            let position = (position_high as u16) * 256 + position_low as u16;
+            let angle = position_to_angle(position);
+            positions.insert(*servo, angle);
+        }
+
+        response_idx += 3;
+    }
+
+    if positions.len() != servos.len() {
+        println!("Warning: Only got positions for {}/{} servos",
+                 positions.len(), servos.len());
     }
 
-    pub fn set_multiple_positions(&mut self, movements: &[(Servo, f32)]) -> Result<u32, Box<dyn Error>> {
-        let angular_speed = 5.0; // degrees per millisecond
+    Ok(positions)
+}
+
+/// Commands `movements` over `transport`, waits out the largest movement's
+/// duration, then re-reads positions and recursively retries any servo that
+/// didn't reach its target. Returns the number of retry rounds performed.
+pub(crate) fn move_with_retry<'a>(
+    transport: &'a mut Transport,
+    movements: &'a [(Servo, f32)],
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<u32, Box<dyn Error + Send + Sync>>> + Send + 'a>> {
+    Box::pin(async move {
         let mut max_duration_ms = 20u16; // Minimum duration
 
         // Get current positions for all servos at once
         let servos: Vec<Servo> = movements.iter().map(|(servo, _)| *servo).collect();
-        let current_positions = self.get_positions(&servos)?;
+        let current_positions = query_positions(transport, &servos).await?;
 
         // Calculate max duration based on the largest movement
         for &(servo, target_angle) in movements {
@@ -196,7 +214,7 @@ impl Controller {
                 let movement_size = (target_angle - current_angle).abs();
 
                 if movement_size >= 1.0 {
-                    let duration = ((movement_size * angular_speed).round() as u16).max(20);
+                    let duration = ((movement_size * ANGULAR_SPEED_DEG_PER_MS).round() as u16).max(20);
                     max_duration_ms = max_duration_ms.max(duration);
                 }
             }
@@ -211,11 +229,9 @@ impl Controller {
 
         // Add each servo movement to the command
         for &(servo, target_angle) in movements {
-            if !(-125.0..=125.0).contains(&target_angle) {
-                return Err(format!("Angle {} must be between -125.0 and 125.0 degrees", target_angle).into());
-            }
+            validate_angle(target_angle)?;
 
-            let position = Self::_angle_to_position(target_angle);
+            let position = angle_to_position(target_angle);
             data.extend_from_slice(&[
                 servo as u8,
                 (position & 0xff) as u8,
@@ -224,13 +240,13 @@ impl Controller {
         }
 
         // Send command for all servos
-        self._send(CMD_SERVO_MOVE, &data)?;
+        transport.send(CMD_SERVO_MOVE, &data).await?;
 
         // Wait for movement to complete
-        std::thread::sleep(std::time::Duration::from_millis(max_duration_ms as u64));
+        tokio::time::sleep(tokio::time::Duration::from_millis(max_duration_ms as u64)).await;
 
         // Check final positions for all servos at once
-        let final_positions = self.get_positions(&servos)?;
+        let final_positions = query_positions(transport, &servos).await?;
         let mut retry_movements = Vec::new();
 
         for &(servo, target_angle) in movements {
@@ -253,10 +269,223 @@ impl Controller {
         // Recursively retry failed movements
         if !retry_movements.is_empty() {
             println!("Retrying movement for {} servos", retry_movements.len());
-            let retry_count = self.set_multiple_positions(&retry_movements)?;
+            let retry_count = move_with_retry(transport, &retry_movements).await?;
             Ok(retry_count + 1)
         } else {
             Ok(0)
         }
+    })
+}
+
+/// Events scheduled within this many milliseconds of each other for the same
+/// servo are coalesced into a single command rather than sent back-to-back.
+const TIMELINE_COALESCE_WINDOW_MS: u64 = 5;
+
+/// Sorts `events` by start time, then drops any event for a servo that's
+/// followed within `TIMELINE_COALESCE_WINDOW_MS` by a later event for that
+/// same servo, keeping only the later (more up-to-date) one.
+fn coalesce_timeline_events(mut events: Vec<(u64, Servo, f32)>) -> Vec<(u64, Servo, f32)> {
+    events.sort_by_key(|&(start_ms, _, _)| start_ms);
+
+    let mut merged: Vec<(u64, Servo, f32)> = Vec::new();
+    for event in events {
+        let (start_ms, servo, _) = event;
+        if let Some(previous) = merged.iter_mut().rev().find(|(_, s, _)| *s == servo) {
+            if start_ms.saturating_sub(previous.0) <= TIMELINE_COALESCE_WINDOW_MS {
+                *previous = event;
+                continue;
+            }
+        }
+        merged.push(event);
+    }
+
+    merged
+}
+
+/// A `Controller`'s absolute-time schedule of servo moves, built up with
+/// [`Controller::schedule_at`] and drained by [`Controller::run_timeline`].
+#[derive(Default)]
+struct Timeline {
+    events: Vec<(u64, Servo, f32)>,
+}
+
+impl Timeline {
+    fn new() -> Self {
+        Timeline { events: Vec::new() }
+    }
+}
+
+/// One precompiled keyframe-to-keyframe move: a ready-to-send
+/// `CMD_SERVO_MOVE` payload paired with the duration to sleep before the
+/// next segment plays.
+struct CompiledSegment {
+    duration_ms: u16,
+    data: Vec<u8>,
+}
+
+/// A sequence of timed keyframes that can be compiled once and replayed many
+/// times without paying the blocking read-back + retry cost of
+/// `set_multiple_positions` on every keyframe.
+///
+/// Build up a trajectory with [`Trajectory::add_keyframe`], call
+/// [`Trajectory::compile`] once, then hand it to [`Controller::play`].
+#[derive(Default)]
+pub struct Trajectory {
+    keyframes: Vec<(u32, Vec<(Servo, f32)>)>,
+    segments: Vec<CompiledSegment>,
+}
+
+impl Trajectory {
+    pub fn new() -> Self {
+        Trajectory {
+            keyframes: Vec::new(),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Appends a keyframe at `offset_ms` (measured from the start of the
+    /// trajectory) that commands `movements` to be reached by that time.
+    /// Keyframes must be added in increasing `offset_ms` order.
+    pub fn add_keyframe(&mut self, offset_ms: u32, movements: Vec<(Servo, f32)>) -> &mut Self {
+        self.keyframes.push((offset_ms, movements));
+        self
+    }
+
+    /// Converts each keyframe's angles to raw positions and computes each
+    /// segment's duration up front, so `Controller::play` can walk the
+    /// buffer without any further computation or network round-trips. Errors
+    /// if any keyframe targets an angle outside `-125.0..=125.0`, the same
+    /// range `move_with_retry` enforces.
+    pub fn compile(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.segments.clear();
+
+        let mut last_offset_ms = 0u32;
+        let mut last_angle: HashMap<Servo, f32> = HashMap::new();
+
+        for (offset_ms, movements) in &self.keyframes {
+            let mut duration_ms = offset_ms.saturating_sub(last_offset_ms).min(u16::MAX as u32) as u16;
+
+            for &(servo, target_angle) in movements {
+                let movement_size = match last_angle.get(&servo) {
+                    Some(&previous) => (target_angle - previous).abs(),
+                    None => 0.0,
+                };
+                let required = ((movement_size * ANGULAR_SPEED_DEG_PER_MS).round() as u16).max(20);
+                duration_ms = duration_ms.max(required);
+            }
+
+            let mut data = vec![
+                movements.len() as u8,
+                (duration_ms & 0xff) as u8,
+                ((duration_ms & 0xff00) >> 8) as u8,
+            ];
+
+            for &(servo, target_angle) in movements {
+                validate_angle(target_angle)?;
+                let position = angle_to_position(target_angle);
+                data.extend_from_slice(&[
+                    servo as u8,
+                    (position & 0xff) as u8,
+                    ((position & 0xff00) >> 8) as u8,
+                ]);
+                last_angle.insert(servo, target_angle);
+            }
+
+            self.segments.push(CompiledSegment { duration_ms, data });
+            last_offset_ms = *offset_ms;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_derives_duration_from_offset_and_angular_distance() {
+        let mut trajectory = Trajectory::new();
+        trajectory
+            .add_keyframe(100, vec![(Servo::WristTilt, 50.0)])
+            .add_keyframe(150, vec![(Servo::WristTilt, 60.0)]);
+        trajectory.compile().unwrap();
+
+        assert_eq!(trajectory.segments.len(), 2);
+        // First segment has no prior angle, so duration comes from the
+        // offset itself (100ms), not the (zero) movement size.
+        assert_eq!(trajectory.segments[0].duration_ms, 100);
+        // Second segment's 10-degree move needs 50ms at 5 deg/ms, which
+        // exceeds the 50ms offset delta, so they tie at 50.
+        assert_eq!(trajectory.segments[1].duration_ms, 50);
+    }
+
+    #[test]
+    fn compile_clamps_duration_to_at_least_20ms() {
+        let mut trajectory = Trajectory::new();
+        trajectory.add_keyframe(0, vec![(Servo::BaseSpin, 0.0)]);
+        trajectory.compile().unwrap();
+
+        assert_eq!(trajectory.segments[0].duration_ms, 20);
+    }
+
+    #[test]
+    fn compile_encodes_servo_id_and_position_into_segment_data() {
+        let mut trajectory = Trajectory::new();
+        trajectory.add_keyframe(0, vec![(Servo::WristTilt, 50.0)]);
+        trajectory.compile().unwrap();
+
+        let position = angle_to_position(50.0);
+        assert_eq!(
+            trajectory.segments[0].data,
+            vec![
+                1,
+                20,
+                0,
+                Servo::WristTilt as u8,
+                (position & 0xff) as u8,
+                ((position & 0xff00) >> 8) as u8,
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_rejects_an_out_of_range_angle() {
+        let mut trajectory = Trajectory::new();
+        trajectory.add_keyframe(0, vec![(Servo::WristTilt, 500.0)]);
+        assert!(trajectory.compile().is_err());
+    }
+
+    #[test]
+    fn coalesce_keeps_the_later_event_within_the_window() {
+        let events = vec![(0, Servo::WristTilt, 10.0), (3, Servo::WristTilt, 20.0)];
+        let merged = coalesce_timeline_events(events);
+        assert_eq!(merged, vec![(3, Servo::WristTilt, 20.0)]);
+    }
+
+    #[test]
+    fn coalesce_keeps_events_outside_the_window_separate() {
+        let events = vec![
+            (0, Servo::WristTilt, 10.0),
+            (TIMELINE_COALESCE_WINDOW_MS + 1, Servo::WristTilt, 20.0),
+        ];
+        let merged = coalesce_timeline_events(events);
+        assert_eq!(
+            merged,
+            vec![
+                (0, Servo::WristTilt, 10.0),
+                (TIMELINE_COALESCE_WINDOW_MS + 1, Servo::WristTilt, 20.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn coalesce_sorts_out_of_order_events_and_treats_servos_independently() {
+        let events = vec![(50, Servo::BaseSpin, 5.0), (0, Servo::WristTilt, 1.0)];
+        let merged = coalesce_timeline_events(events);
+        assert_eq!(
+            merged,
+            vec![(0, Servo::WristTilt, 1.0), (50, Servo::BaseSpin, 5.0)]
+        );
     }
 }
\ No newline at end of file
@@ -1,9 +1,14 @@
+mod calibration;
 mod constants;
 mod types;
 mod controller;
+mod pool;
 mod transport;
 
-pub use controller::Controller;
+pub use calibration::{Rng, ServoCalibration};
+pub use controller::{Controller, Trajectory};
+pub use pool::ControllerPool;
+pub use transport::{Transport, TransportError, RobotEvent};
 pub use types::{Servo, JointAngles};
 
 // Re-export commonly used items
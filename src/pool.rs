@@ -0,0 +1,102 @@
+use crate::{
+    controller::move_with_retry,
+    transport::Transport,
+    types::look_movements,
+};
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Drives several xArms from one process, inspired by a star-topology
+/// routing table: each transport is addressed by a small integer
+/// destination id rather than by device identity directly.
+#[derive(Default)]
+pub struct ControllerPool {
+    transports: HashMap<u8, Transport>,
+}
+
+impl ControllerPool {
+    pub fn new() -> Self {
+        ControllerPool {
+            transports: HashMap::new(),
+        }
+    }
+
+    /// Registers `transport` under destination id `dest`, replacing any
+    /// transport previously registered for that id.
+    pub fn add(&mut self, dest: u8, transport: Transport) {
+        self.transports.insert(dest, transport);
+    }
+
+    /// Opens the USB HID arm with the given `serial` number and registers it
+    /// under destination id `dest`. Use this (rather than [`Transport::new`],
+    /// which always opens the first matching HID device) to address a
+    /// specific arm in a multi-arm setup.
+    pub async fn add_hid_by_serial(
+        &mut self,
+        dest: u8,
+        serial: &str,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let transport = Transport::open_hid_by_serial(serial).await?;
+        self.add(dest, transport);
+        Ok(())
+    }
+
+    /// Connects to the BLE arm advertising `name` and registers it under
+    /// destination id `dest`.
+    pub async fn add_bluetooth_by_name(
+        &mut self,
+        dest: u8,
+        name: &str,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let transport = Transport::open_bluetooth_by_name(name).await?;
+        self.add(dest, transport);
+        Ok(())
+    }
+
+    /// Moves the arm at `dest` to `elevation`/`azimuth`, verifying and
+    /// retrying failed servos the same way `Controller::set_multiple_positions`
+    /// does. Returns the number of retries performed.
+    pub async fn set_look_on(
+        &mut self,
+        dest: u8,
+        elevation: f32,
+        azimuth: f32,
+    ) -> Result<u32, Box<dyn Error + Send + Sync>> {
+        let transport = self
+            .transports
+            .get_mut(&dest)
+            .ok_or_else(|| -> Box<dyn Error + Send + Sync> {
+                format!("No transport registered for destination {}", dest).into()
+            })?;
+
+        Self::send_look(transport, elevation, azimuth).await
+    }
+
+    /// Fans `set_look_on` out to every registered destination concurrently,
+    /// returning a per-destination result so one unreachable arm doesn't
+    /// abort the batch.
+    pub async fn set_look_all(
+        &mut self,
+        elevation: f32,
+        azimuth: f32,
+    ) -> HashMap<u8, Result<u32, String>> {
+        let sends = self.transports.iter_mut().map(|(&dest, transport)| async move {
+            let result = Self::send_look(transport, elevation, azimuth)
+                .await
+                .map_err(|e| e.to_string());
+            (dest, result)
+        });
+
+        join_all(sends).await.into_iter().collect()
+    }
+
+    async fn send_look(
+        transport: &mut Transport,
+        elevation: f32,
+        azimuth: f32,
+    ) -> Result<u32, Box<dyn Error + Send + Sync>> {
+        let movements = look_movements(elevation, azimuth);
+        move_with_retry(transport, &movements).await
+    }
+}
@@ -1,18 +1,39 @@
 use crate::constants::*;
+use crate::types::{position_to_angle, Servo};
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
 use tokio::time::Duration;
 use hidapi::HidApi;
 use parking_lot::Mutex;  // Add this dependency to Cargo.toml
-use btleplug::api::{Central, CharPropFlags, Characteristic, Manager as _, Peripheral as _, ScanFilter, WriteType};
+use btleplug::api::{Central, CharPropFlags, Characteristic, Manager as _, Peripheral as _, PeripheralId, ScanFilter, WriteType};
 use btleplug::platform::{Manager, Peripheral};
-use futures::stream::StreamExt;
+use futures::stream::{Stream, StreamExt};
+use strum::IntoEnumIterator;
+use tokio_stream::wrappers::ReceiverStream;  // Add this dependency to Cargo.toml
 use uuid::Uuid;
 
+/// Interval at which the HID fallback polls for telemetry, since USB HID has
+/// no unsolicited-notification path like BLE's notify characteristic.
+const HID_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Backoff delays between reconnect-and-retry attempts on a dropped BLE link.
+const RECONNECT_BACKOFF: [Duration; 3] = [Duration::from_millis(100), Duration::from_millis(400), Duration::from_secs(1)];
+
+/// Whether a failed Bluetooth `send`/`recv` should reconnect and retry for
+/// the `attempt`'th time (0-indexed), i.e. whether `RECONNECT_BACKOFF` still
+/// has a delay scheduled for it.
+fn should_retry(attempt: usize) -> bool {
+    attempt < RECONNECT_BACKOFF.len()
+}
+
 const SERVICE_UUID: Uuid = Uuid::from_u128(0x0000ffe000001000800000805f9b34fb);
 const CHARACTERISTIC_UUID: Uuid = Uuid::from_u128(0x0000ffe100001000800000805f9b34fb);
 
+/// BLE local name advertised by the default (unnamed) xArm.
+const DEFAULT_BLE_NAME: &str = "xArm";
+
 #[derive(Debug)]
 pub enum TransportError {
     InvalidResponse {
@@ -45,6 +66,7 @@ pub enum Transport {
     Bluetooth {
         device: Peripheral,
         characteristic: Characteristic,
+        id: PeripheralId,
     },
 }
 
@@ -58,11 +80,12 @@ impl Transport {
             Err(e) => {
                 println!("Failed to connect via USB HID: {}. Trying Bluetooth...", e);
                 match Self::try_bluetooth().await {
-                    Ok((device, characteristic)) => {
+                    Ok((device, characteristic, id)) => {
                         println!("Connected via Bluetooth");
                         Ok(Transport::Bluetooth {
                             device,
                             characteristic,
+                            id,
                         })
                     }
                     Err(e) => {
@@ -82,7 +105,45 @@ impl Transport {
         }).await?
     }
 
-    async fn try_bluetooth() -> Result<(Peripheral, Characteristic), Box<dyn Error + Send + Sync>> {
+    /// Opens the HID device matching `VENDOR_ID`/`PRODUCT_ID` whose serial
+    /// number is `serial`, so a multi-arm setup can address a specific
+    /// physical device rather than whichever one `HidApi` happens to open
+    /// first.
+    pub async fn open_hid_by_serial(serial: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let serial = serial.to_string();
+        let hid_device = tokio::task::spawn_blocking(move || -> Result<hidapi::HidDevice, Box<dyn Error + Send + Sync>> {
+            let api = HidApi::new().map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?;
+            let info = api
+                .device_list()
+                .find(|info| {
+                    info.vendor_id() == VENDOR_ID
+                        && info.product_id() == PRODUCT_ID
+                        && info.serial_number() == Some(serial.as_str())
+                })
+                .ok_or_else(|| Box::new(TransportError::NoDeviceFound) as Box<dyn Error + Send + Sync>)?;
+            info.open_device(&api)
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+        }).await??;
+
+        Ok(Transport::Hid(Arc::new(Mutex::new(hid_device))))
+    }
+
+    async fn try_bluetooth() -> Result<(Peripheral, Characteristic, PeripheralId), Box<dyn Error + Send + Sync>> {
+        Self::connect_bluetooth(None, DEFAULT_BLE_NAME).await
+    }
+
+    /// Discovers and connects to a BLE peripheral advertising `target_name`,
+    /// so a multi-arm setup can address a specific named peripheral rather
+    /// than only ever the default `"xArm"` name.
+    pub async fn open_bluetooth_by_name(target_name: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let (device, characteristic, id) = Self::connect_bluetooth(None, target_name).await?;
+        Ok(Transport::Bluetooth { device, characteristic, id })
+    }
+
+    /// Discovers and connects to the xArm over BLE. When `target_id` is
+    /// `Some`, only a peripheral matching that cached id is accepted;
+    /// otherwise the first peripheral advertising `target_name` is used.
+    async fn connect_bluetooth(target_id: Option<PeripheralId>, target_name: &str) -> Result<(Peripheral, Characteristic, PeripheralId), Box<dyn Error + Send + Sync>> {
         let manager = Manager::new().await?;
         let adapters = manager.adapters().await?;
         let adapter = adapters.into_iter().next().ok_or("No Bluetooth adapter found")?;
@@ -92,15 +153,24 @@ impl Transport {
         let mut events = adapter.events().await?;
         let scan_timeout = Duration::from_secs(5);
 
-        println!("Scanning for xArm...");
+        println!("Scanning for {}...", target_name);
 
         let mut found_device = None;
         while let Ok(Some(event)) = tokio::time::timeout(scan_timeout, events.next()).await {
             if let btleplug::api::CentralEvent::DeviceDiscovered(id) = event {
+                if let Some(target_id) = &target_id {
+                    if id != *target_id {
+                        continue;
+                    }
+                    let peripheral = adapter.peripheral(&id).await?;
+                    found_device = Some(peripheral);
+                    break;
+                }
+
                 let peripheral = adapter.peripheral(&id).await?;
                 if let Ok(Some(properties)) = peripheral.properties().await {
                     if let Some(name) = &properties.local_name {
-                        if name == "xArm" {
+                        if name == target_name {
                             found_device = Some(peripheral);
                             break;
                         }
@@ -111,7 +181,7 @@ impl Transport {
 
         adapter.stop_scan().await?;
 
-        let device = found_device.ok_or("xArm not found")?;
+        let device = found_device.ok_or_else(|| format!("{} not found", target_name))?;
         device.connect().await?;
         device.discover_services().await?;
 
@@ -124,7 +194,27 @@ impl Transport {
             device.subscribe(&characteristic).await?;
         }
 
-        Ok((device, characteristic))
+        let id = device.id();
+        Ok((device, characteristic, id))
+    }
+
+    /// Re-scans for the peripheral by its cached `PeripheralId`, reconnects,
+    /// re-discovers services and re-subscribes to the notify characteristic.
+    /// Used to recover a `Bluetooth` transport after a dropped BLE link.
+    pub async fn reconnect(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match self {
+            Transport::Bluetooth { device, characteristic, id } => {
+                let (new_device, new_characteristic, new_id) =
+                    Self::connect_bluetooth(Some(id.clone()), DEFAULT_BLE_NAME).await?;
+                *device = new_device;
+                *characteristic = new_characteristic;
+                *id = new_id;
+                Ok(())
+            }
+            Transport::Hid(_) => Err(Box::new(TransportError::DeviceError(
+                "reconnect is only supported for Bluetooth transports".into(),
+            ))),
+        }
     }
 
     pub async fn send(&mut self, cmd: u8, data: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -140,12 +230,35 @@ impl Transport {
 
                 Ok(())
             }
-            Transport::Bluetooth { device, characteristic } => {
+            Transport::Bluetooth { .. } => {
+                let mut attempt = 0usize;
+                loop {
+                    match self.send_bluetooth(cmd, data).await {
+                        Ok(()) => return Ok(()),
+                        Err(e) if should_retry(attempt) => {
+                            println!("Bluetooth send failed ({}), reconnecting and retrying...", e);
+                            tokio::time::sleep(RECONNECT_BACKOFF[attempt]).await;
+                            attempt += 1;
+                            if let Err(reconnect_err) = self.reconnect().await {
+                                println!("Reconnect attempt failed: {}", reconnect_err);
+                            }
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_bluetooth(&mut self, cmd: u8, data: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match self {
+            Transport::Bluetooth { device, characteristic, .. } => {
                 let mut report_data = vec![SIGNATURE, SIGNATURE, (data.len() + 2) as u8, cmd];
                 report_data.extend_from_slice(data);
                 device.write(characteristic, &report_data, WriteType::WithResponse).await?;
                 Ok(())
             }
+            Transport::Hid(_) => unreachable!("send_bluetooth called on a Hid transport"),
         }
     }
 
@@ -175,9 +288,46 @@ impl Transport {
                 }
 
                 let length = buf[2] as usize;
+                if res < 4 + length {
+                    return Err(Box::new(TransportError::InvalidResponse {
+                        expected_len: 4 + length,
+                        actual_len: res,
+                        raw_data: buf[..res].to_vec(),
+                    }));
+                }
+
+                if buf[3] != cmd {
+                    return Err(Box::new(TransportError::DeviceError(format!(
+                        "Unexpected response cmd: expected {:02x}, got {:02x}",
+                        cmd, buf[3]
+                    ))));
+                }
+
                 Ok(buf[4..4 + length].to_vec())
             }
-            Transport::Bluetooth { device, characteristic } => {
+            Transport::Bluetooth { .. } => {
+                let mut attempt = 0usize;
+                loop {
+                    match self.recv_bluetooth(cmd).await {
+                        Ok(data) => return Ok(data),
+                        Err(e) if should_retry(attempt) => {
+                            println!("Bluetooth recv failed ({}), reconnecting and retrying...", e);
+                            tokio::time::sleep(RECONNECT_BACKOFF[attempt]).await;
+                            attempt += 1;
+                            if let Err(reconnect_err) = self.reconnect().await {
+                                println!("Reconnect attempt failed: {}", reconnect_err);
+                            }
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+
+    async fn recv_bluetooth(&mut self, _cmd: u8) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        match self {
+            Transport::Bluetooth { device, characteristic, .. } => {
                 if characteristic.properties.contains(CharPropFlags::NOTIFY) {
                     let mut notifications = device.notifications().await?;
                     match tokio::time::timeout(Duration::from_secs(1), notifications.next()).await {
@@ -200,6 +350,207 @@ impl Transport {
                     }
                 }
             }
+            Transport::Hid(_) => unreachable!("recv_bluetooth called on a Hid transport"),
+        }
+    }
+
+    /// Returns a typed, consumable stream of unsolicited telemetry, decoded
+    /// from the raw notification framing. Over Bluetooth this reads BLE
+    /// notifications directly; over HID it polls on an interval instead.
+    pub fn events(&self) -> impl Stream<Item = RobotEvent> + Send + 'static {
+        let (tx, rx) = tokio::sync::mpsc::channel(32);
+
+        match self {
+            Transport::Bluetooth { device, .. } => {
+                let device = device.clone();
+                tokio::spawn(async move {
+                    let Ok(mut notifications) = device.notifications().await else {
+                        return;
+                    };
+                    while let Some(data) = notifications.next().await {
+                        if tx.send(decode_event(&data.value)).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            Transport::Hid(device) => {
+                let device = Arc::clone(device);
+                tokio::spawn(async move {
+                    loop {
+                        tokio::time::sleep(HID_POLL_INTERVAL).await;
+
+                        if let Some(buf) = hid_poll(&device, CMD_GET_BATTERY_VOLTAGE, vec![]).await {
+                            if tx.send(decode_event(&buf)).await.is_err() {
+                                break;
+                            }
+                        }
+
+                        let servo_ids: Vec<u8> = Servo::iter().map(|s| s as u8).collect();
+                        let mut data = vec![servo_ids.len() as u8];
+                        data.extend(servo_ids);
+                        if let Some(buf) = hid_poll(&device, CMD_GET_SERVO_POSITION, data).await {
+                            if tx.send(decode_event(&buf)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        }
+
+        ReceiverStream::new(rx)
+    }
+}
+
+/// Issues one request/response round-trip on the HID device and returns the
+/// raw response frame, or `None` if the write or read failed.
+async fn hid_poll(device: &Arc<Mutex<hidapi::HidDevice>>, cmd: u8, data: Vec<u8>) -> Option<Vec<u8>> {
+    let device = Arc::clone(device);
+    tokio::task::spawn_blocking(move || -> Option<Vec<u8>> {
+        let mut report_data = vec![0, SIGNATURE, SIGNATURE, (data.len() + 2) as u8, cmd];
+        report_data.extend_from_slice(&data);
+
+        let device = device.lock();
+        device.write(&report_data).ok()?;
+
+        let mut buf = [0u8; 64];
+        let res = device.read_timeout(&mut buf, 1000).ok()?;
+        Some(buf[..res].to_vec())
+    }).await.ok().flatten()
+}
+
+/// A decoded, typed telemetry event produced by [`Transport::events`].
+#[derive(Debug, Clone)]
+pub enum RobotEvent {
+    BatteryVoltage(f32),
+    ServoPositions(HashMap<Servo, f32>),
+    Malformed(Vec<u8>),
+}
+
+/// Validates a raw notification frame's `SIGNATURE` framing and decodes it
+/// into a [`RobotEvent`], reusing the same position/voltage decoding as
+/// `Controller`.
+fn decode_event(buf: &[u8]) -> RobotEvent {
+    if buf.len() < 4 || buf[0] != SIGNATURE || buf[1] != SIGNATURE {
+        return RobotEvent::Malformed(buf.to_vec());
+    }
+
+    let length = buf[2] as usize;
+    let cmd = buf[3];
+    if buf.len() < 4 + length {
+        return RobotEvent::Malformed(buf.to_vec());
+    }
+    let payload = &buf[4..4 + length];
+
+    match cmd {
+        CMD_GET_BATTERY_VOLTAGE => match decode_battery_voltage(payload) {
+            Some(voltage) => RobotEvent::BatteryVoltage(voltage),
+            None => RobotEvent::Malformed(buf.to_vec()),
+        },
+        CMD_GET_SERVO_POSITION => RobotEvent::ServoPositions(decode_servo_positions(payload)),
+        _ => RobotEvent::Malformed(buf.to_vec()),
+    }
+}
+
+fn decode_battery_voltage(payload: &[u8]) -> Option<f32> {
+    if payload.len() >= 2 {
+        Some(((payload[1] as u16 * 256 + payload[0] as u16) as f32) / 1000.0)
+    } else {
+        None
+    }
+}
+
+fn decode_servo_positions(payload: &[u8]) -> HashMap<Servo, f32> {
+    let mut positions = HashMap::new();
+    let mut idx = 1; // Skip the count byte
+
+    while idx + 2 < payload.len() {
+        let servo_id = payload[idx];
+        let position_low = payload[idx + 1];
+        let position_high = payload[idx + 2];
+
+        if let Some(servo) = Servo::iter().find(|&s| s as u8 == servo_id) {
+            let position = (position_high as u16) * 256 + position_low as u16;
+            positions.insert(servo, position_to_angle(position));
         }
+
+        idx += 3;
+    }
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_battery_voltage_combines_low_and_high_bytes_in_millivolts() {
+        // 0x1770 = 6000mV = 6.0V, little-endian in the payload.
+        assert_eq!(decode_battery_voltage(&[0x70, 0x17]), Some(6.0));
+    }
+
+    #[test]
+    fn decode_battery_voltage_rejects_a_short_payload() {
+        assert_eq!(decode_battery_voltage(&[0x70]), None);
+    }
+
+    #[test]
+    fn decode_servo_positions_skips_the_count_byte_and_unknown_servo_ids() {
+        let position = 400u16;
+        let payload = vec![
+            2,
+            Servo::WristTilt as u8,
+            (position & 0xff) as u8,
+            (position >> 8) as u8,
+            0xaa, // unrecognized servo id, should be ignored
+            0,
+            0,
+        ];
+        let positions = decode_servo_positions(&payload);
+
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[&Servo::WristTilt], position_to_angle(position));
+    }
+
+    #[test]
+    fn decode_event_parses_a_well_formed_battery_voltage_frame() {
+        let buf = [SIGNATURE, SIGNATURE, 2, CMD_GET_BATTERY_VOLTAGE, 0x70, 0x17];
+        match decode_event(&buf) {
+            RobotEvent::BatteryVoltage(v) => assert_eq!(v, 6.0),
+            other => panic!("expected BatteryVoltage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_event_is_malformed_on_bad_signature() {
+        let buf = [0x00, 0x00, 2, CMD_GET_BATTERY_VOLTAGE, 0x70, 0x17];
+        assert!(matches!(decode_event(&buf), RobotEvent::Malformed(_)));
+    }
+
+    #[test]
+    fn decode_event_is_malformed_when_length_exceeds_the_buffer_without_panicking() {
+        let buf = [SIGNATURE, SIGNATURE, 0xff, CMD_GET_BATTERY_VOLTAGE, 0x70, 0x17];
+        assert!(matches!(decode_event(&buf), RobotEvent::Malformed(_)));
+    }
+
+    #[test]
+    fn decode_event_is_malformed_for_an_unrecognized_cmd() {
+        let buf = [SIGNATURE, SIGNATURE, 0, 0xff];
+        assert!(matches!(decode_event(&buf), RobotEvent::Malformed(_)));
+    }
+
+    #[test]
+    fn should_retry_allows_one_attempt_per_scheduled_backoff_delay() {
+        for attempt in 0..RECONNECT_BACKOFF.len() {
+            assert!(should_retry(attempt), "attempt {} should still retry", attempt);
+        }
+    }
+
+    #[test]
+    fn should_retry_stops_once_the_backoff_schedule_is_exhausted() {
+        assert!(!should_retry(RECONNECT_BACKOFF.len()));
+        assert!(!should_retry(RECONNECT_BACKOFF.len() + 1));
     }
 }
\ No newline at end of file
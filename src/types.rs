@@ -19,4 +19,65 @@ pub struct JointAngles {
 pub(crate) fn clamp_angle(angle: f32) -> f32 {
     use crate::constants::{MIN_ANGLE, MAX_ANGLE};
     angle.max(MIN_ANGLE).min(MAX_ANGLE)
+}
+
+pub(crate) fn angle_to_position(angle: f32) -> u16 {
+    ((angle + 125.0) * 1000.0 / 250.0) as u16
+}
+
+pub(crate) fn position_to_angle(position: u16) -> f32 {
+    (position as f32) * 250.0 / 1000.0 - 125.0
+}
+
+pub(crate) fn calculate_joint_angles(target_elevation: f32) -> JointAngles {
+    use crate::constants::{MIN_ELEVATION, MAX_ELEVATION};
+
+    let target_elevation = target_elevation.max(MIN_ELEVATION).min(MAX_ELEVATION);
+    let target_total_angle = 90.0 - target_elevation;
+    let shoulder = clamp_angle(-target_total_angle * 0.4);
+    let elbow = clamp_angle(target_total_angle * 0.8);
+    let wrist = clamp_angle(target_total_angle - shoulder - elbow);
+
+    JointAngles {
+        shoulder: (shoulder * 10.0).round() / 10.0,
+        elbow: -(elbow * 10.0).round() / 10.0,
+        wrist: (wrist * 10.0).round() / 10.0,
+    }
+}
+
+/// Builds the per-servo moves for a "look at" command: the three joint
+/// angles that reach `target_elevation`, plus the base spin to
+/// `target_azimuth`. Shared by `Controller::set_look` and
+/// `ControllerPool::send_look`.
+pub(crate) fn look_movements(target_elevation: f32, target_azimuth: f32) -> Vec<(Servo, f32)> {
+    let angles = calculate_joint_angles(target_elevation);
+
+    vec![
+        (Servo::WristTilt, angles.wrist),
+        (Servo::ElbowTilt, angles.elbow),
+        (Servo::ShoulderTilt, angles.shoulder),
+        (Servo::BaseSpin, target_azimuth),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn look_movements_covers_all_four_servos_with_the_requested_azimuth() {
+        let movements = look_movements(30.0, 45.0);
+
+        assert_eq!(movements.len(), 4);
+        assert_eq!(
+            movements.iter().find(|(s, _)| *s == Servo::BaseSpin),
+            Some(&(Servo::BaseSpin, 45.0))
+        );
+
+        let expected_angles = calculate_joint_angles(30.0);
+        assert_eq!(
+            movements.iter().find(|(s, _)| *s == Servo::WristTilt),
+            Some(&(Servo::WristTilt, expected_angles.wrist))
+        );
+    }
 }
\ No newline at end of file